@@ -0,0 +1,23 @@
+// 笔记存储位置等运行时配置
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::notes::NotesStore;
+
+#[derive(Serialize)]
+pub struct AppConfig {
+    pub storage_dir: String,
+}
+
+#[tauri::command]
+pub fn get_config(store: State<NotesStore>) -> Result<AppConfig, String> {
+    Ok(AppConfig {
+        storage_dir: store.storage_dir()?.to_string_lossy().into_owned(),
+    })
+}
+
+#[tauri::command]
+pub fn set_storage_dir(store: State<NotesStore>, path: String) -> Result<(), String> {
+    store.set_storage_dir(path.into())
+}
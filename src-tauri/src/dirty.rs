@@ -0,0 +1,21 @@
+// 跟踪当前笔记是否存在未保存的修改
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Default)]
+pub struct DirtyState(AtomicBool);
+
+impl DirtyState {
+    pub fn is_dirty(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn set(&self, dirty: bool) {
+        self.0.store(dirty, Ordering::SeqCst);
+    }
+}
+
+#[tauri::command]
+pub fn set_dirty_state(state: tauri::State<DirtyState>, dirty: bool) {
+    state.set(dirty);
+}
@@ -1,9 +1,50 @@
 // 防止 Windows release 版本出现额外控制台窗口
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod config;
+mod dirty;
+mod notes;
+mod search;
+mod tray;
+
+use tauri::{Emitter, Manager, WindowEvent};
+
 fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_devtools::init())
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            tray::show_main_window(app);
+        }))
+        .manage(dirty::DirtyState::default())
+        .setup(|app| {
+            app.manage(notes::init(app.handle()));
+            app.set_menu(tray::build_menu(app.handle())?)?;
+            tray::build_tray(app.handle())?;
+            Ok(())
+        })
+        .on_menu_event(|app, event| tray::handle_menu_event(app, event))
+        .on_window_event(|window, event| {
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                let state = window.state::<dirty::DirtyState>();
+                if state.is_dirty() {
+                    api.prevent_close();
+                    let _ = window.emit("window://confirm-close", ());
+                } else {
+                    let _ = window.hide();
+                    api.prevent_close();
+                }
+            }
+        })
+        .invoke_handler(tauri::generate_handler![
+            notes::create_note,
+            notes::list_notes,
+            notes::update_note,
+            notes::delete_note,
+            notes::search_notes,
+            dirty::set_dirty_state,
+            config::get_config,
+            config::set_storage_dir,
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
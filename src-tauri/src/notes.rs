@@ -0,0 +1,555 @@
+// 每日笔记的数据模型与本地持久化
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::search::{self, SearchHit};
+
+const STORE_FILE: &str = "notes.json";
+const STORAGE_DIR_ENV: &str = "DAILY_NOTE_DIR";
+
+/// term -> note id -> term frequency
+type Index = HashMap<String, HashMap<String, u32>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Note {
+    pub id: String,
+    pub content: String,
+    pub tag: Option<String>,
+    pub date: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+struct NotesFile {
+    notes: HashMap<String, Note>,
+}
+
+struct StoreData {
+    dir: PathBuf,
+    file: NotesFile,
+    index: Index,
+}
+
+pub struct NotesStore {
+    inner: Mutex<StoreData>,
+}
+
+impl NotesStore {
+    pub fn load(dir: PathBuf) -> Self {
+        let file = read_notes_file(&dir).unwrap_or_default();
+        let index = rebuild_index(&file);
+        Self {
+            inner: Mutex::new(StoreData { dir, file, index }),
+        }
+    }
+
+    fn persist(&self, data: &StoreData) -> Result<(), String> {
+        fs::create_dir_all(&data.dir).map_err(|e| e.to_string())?;
+        let raw = serde_json::to_string_pretty(&data.file).map_err(|e| e.to_string())?;
+        fs::write(data.dir.join(STORE_FILE), raw).map_err(|e| e.to_string())
+    }
+
+    pub fn storage_dir(&self) -> Result<PathBuf, String> {
+        Ok(self.inner.lock().map_err(|e| e.to_string())?.dir.clone())
+    }
+
+    /// Points the store at `new_dir`, e.g. a Dropbox/iCloud folder that may
+    /// not exist yet or may already hold notes synced from another machine.
+    /// Existing notes at the destination are merged in (newest `updated_at`
+    /// wins per id) rather than overwritten. The merge is built on a scratch
+    /// copy and only swapped into `data` after it has been written to
+    /// `new_dir` successfully, so a failed write (e.g. a read-only synced
+    /// placeholder) leaves the store byte-for-byte unchanged, still pointed
+    /// at the old directory.
+    pub fn set_storage_dir(&self, new_dir: PathBuf) -> Result<(), String> {
+        let mut data = self.inner.lock().map_err(|e| e.to_string())?;
+        if new_dir == data.dir {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&new_dir).map_err(|e| e.to_string())?;
+
+        let mut merged = data.file.clone();
+        if let Some(existing) = read_notes_file(&new_dir) {
+            merge_notes_file(&mut merged, existing);
+        }
+
+        let raw = serde_json::to_string_pretty(&merged).map_err(|e| e.to_string())?;
+        fs::write(new_dir.join(STORE_FILE), raw).map_err(|e| e.to_string())?;
+
+        data.index = rebuild_index(&merged);
+        data.file = merged;
+        data.dir = new_dir;
+        Ok(())
+    }
+
+    fn create(&self, content: String, tag: Option<String>, date: String) -> Result<Note, String> {
+        let mut data = self.inner.lock().map_err(|e| e.to_string())?;
+        let now = now_ts();
+        let note = Note {
+            id: new_id(),
+            content,
+            tag,
+            date,
+            created_at: now,
+            updated_at: now,
+        };
+        data.file.notes.insert(note.id.clone(), note.clone());
+        add_to_index(&mut data.index, &note);
+        self.persist(&data)?;
+        Ok(note)
+    }
+
+    fn list(&self, date_range: (String, String)) -> Result<Vec<Note>, String> {
+        let data = self.inner.lock().map_err(|e| e.to_string())?;
+        let (from, to) = date_range;
+        let mut notes: Vec<Note> = data
+            .file
+            .notes
+            .values()
+            .filter(|n| n.date.as_str() >= from.as_str() && n.date.as_str() <= to.as_str())
+            .cloned()
+            .collect();
+        notes.sort_by(|a, b| a.date.cmp(&b.date).then(a.created_at.cmp(&b.created_at)));
+        Ok(notes)
+    }
+
+    fn update(&self, id: String, content: String) -> Result<Note, String> {
+        let mut data = self.inner.lock().map_err(|e| e.to_string())?;
+        let existing = data
+            .file
+            .notes
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| format!("note {id} not found"))?;
+        remove_from_index(&mut data.index, &existing);
+
+        let note = data.file.notes.get_mut(&id).expect("checked above");
+        note.content = content;
+        note.updated_at = now_ts();
+        let note = note.clone();
+        add_to_index(&mut data.index, &note);
+        self.persist(&data)?;
+        Ok(note)
+    }
+
+    fn delete(&self, id: String) -> Result<(), String> {
+        let mut data = self.inner.lock().map_err(|e| e.to_string())?;
+        let note = data
+            .file
+            .notes
+            .remove(&id)
+            .ok_or_else(|| format!("note {id} not found"))?;
+        remove_from_index(&mut data.index, &note);
+        self.persist(&data)
+    }
+
+    fn search(
+        &self,
+        query: String,
+        tags: Option<Vec<String>>,
+        from_date: String,
+        to_date: String,
+    ) -> Result<Vec<SearchHit>, String> {
+        let data = self.inner.lock().map_err(|e| e.to_string())?;
+        let terms = search::tokenize(&query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut candidates: Option<HashSet<String>> = None;
+        for term in &terms {
+            let postings: HashSet<String> = data
+                .index
+                .get(term)
+                .map(|ids| ids.keys().cloned().collect())
+                .unwrap_or_default();
+            candidates = Some(match candidates {
+                None => postings,
+                Some(prev) => prev.intersection(&postings).cloned().collect(),
+            });
+        }
+
+        let mut hits: Vec<SearchHit> = candidates
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| data.file.notes.get(&id).map(|note| (id, note)))
+            .filter(|(_, note)| {
+                tags.as_ref().map_or(true, |tags| {
+                    note.tag
+                        .as_deref()
+                        .is_some_and(|tag| tags.iter().any(|wanted| wanted == tag))
+                }) && note.date.as_str() >= from_date.as_str()
+                    && note.date.as_str() <= to_date.as_str()
+            })
+            .map(|(id, note)| {
+                let score = terms
+                    .iter()
+                    .filter_map(|term| data.index.get(term).and_then(|ids| ids.get(&id)))
+                    .sum();
+                let (snippet, match_start, match_end) = search::make_snippet(&note.content, &terms);
+                SearchHit {
+                    note_id: id,
+                    score,
+                    snippet,
+                    match_start,
+                    match_end,
+                    created_at: note.created_at,
+                }
+            })
+            .collect();
+
+        hits.sort_by(|a, b| b.score.cmp(&a.score).then(b.created_at.cmp(&a.created_at)));
+        Ok(hits)
+    }
+}
+
+fn read_notes_file(dir: &std::path::Path) -> Option<NotesFile> {
+    let raw = fs::read_to_string(dir.join(STORE_FILE)).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+/// Folds `other` into `file`, keeping the newer note on id collisions so a
+/// relocation never silently discards edits made from another machine.
+fn merge_notes_file(file: &mut NotesFile, other: NotesFile) {
+    for (id, note) in other.notes {
+        match file.notes.entry(id) {
+            std::collections::hash_map::Entry::Vacant(slot) => {
+                slot.insert(note);
+            }
+            std::collections::hash_map::Entry::Occupied(mut slot) => {
+                if note.updated_at > slot.get().updated_at {
+                    slot.insert(note);
+                }
+            }
+        }
+    }
+}
+
+fn add_to_index(index: &mut Index, note: &Note) {
+    for (term, freq) in search::index_terms(&note.content) {
+        index.entry(term).or_default().insert(note.id.clone(), freq);
+    }
+}
+
+fn remove_from_index(index: &mut Index, note: &Note) {
+    for term in search::tokenize(&note.content) {
+        if let Some(postings) = index.get_mut(&term) {
+            postings.remove(&note.id);
+            if postings.is_empty() {
+                index.remove(&term);
+            }
+        }
+    }
+}
+
+fn rebuild_index(file: &NotesFile) -> Index {
+    let mut index = Index::new();
+    for note in file.notes.values() {
+        add_to_index(&mut index, note);
+    }
+    index
+}
+
+fn now_ts() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn new_id() -> String {
+    format!("{:x}-{:x}", now_ts(), rand_suffix())
+}
+
+fn rand_suffix() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0)
+}
+
+/// Resolves where notes live: `DAILY_NOTE_DIR` overrides the OS app-data directory.
+pub fn default_storage_dir(app: &AppHandle) -> PathBuf {
+    if let Ok(dir) = std::env::var(STORAGE_DIR_ENV) {
+        return PathBuf::from(dir);
+    }
+    app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+}
+
+pub fn init(app: &AppHandle) -> NotesStore {
+    NotesStore::load(default_storage_dir(app))
+}
+
+#[tauri::command]
+pub fn create_note(
+    store: State<NotesStore>,
+    content: String,
+    tag: Option<String>,
+    date: String,
+) -> Result<Note, String> {
+    store.create(content, tag, date)
+}
+
+#[tauri::command]
+pub fn list_notes(
+    store: State<NotesStore>,
+    date_range: (String, String),
+) -> Result<Vec<Note>, String> {
+    store.list(date_range)
+}
+
+#[tauri::command]
+pub fn update_note(store: State<NotesStore>, id: String, content: String) -> Result<Note, String> {
+    store.update(id, content)
+}
+
+#[tauri::command]
+pub fn delete_note(store: State<NotesStore>, id: String) -> Result<(), String> {
+    store.delete(id)
+}
+
+#[tauri::command]
+pub fn search_notes(
+    store: State<NotesStore>,
+    query: String,
+    tags: Option<Vec<String>>,
+    from_date: String,
+    to_date: String,
+) -> Result<Vec<SearchHit>, String> {
+    store.search(query, tags, from_date, to_date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let n = COUNTER.fetch_add(1, Ordering::SeqCst);
+        let dir = std::env::temp_dir().join(format!(
+            "daily-note-test-{label}-{}-{}-{n}",
+            std::process::id(),
+            now_ts()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn list_filters_notes_by_date_range_inclusive() {
+        let store = NotesStore::load(temp_dir("list-range"));
+        store.create("before range".into(), None, "2024-01-01".into()).unwrap();
+        let start = store
+            .create("range start".into(), None, "2024-01-02".into())
+            .unwrap();
+        let end = store
+            .create("range end".into(), None, "2024-01-03".into())
+            .unwrap();
+        store.create("after range".into(), None, "2024-01-04".into()).unwrap();
+
+        let notes = store.list(("2024-01-02".into(), "2024-01-03".into())).unwrap();
+        let ids: Vec<_> = notes.iter().map(|n| n.id.clone()).collect();
+        assert_eq!(ids, vec![start.id, end.id]);
+    }
+
+    #[test]
+    fn update_changes_content_and_reindexes_search() {
+        let store = NotesStore::load(temp_dir("update"));
+        let note = store
+            .create("original text".into(), None, "2024-01-01".into())
+            .unwrap();
+
+        let updated = store.update(note.id.clone(), "revised text".into()).unwrap();
+        assert_eq!(updated.content, "revised text");
+
+        let hits = store
+            .search("revised".into(), None, "2024-01-01".into(), "2024-01-01".into())
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        let stale = store
+            .search("original".into(), None, "2024-01-01".into(), "2024-01-01".into())
+            .unwrap();
+        assert!(stale.is_empty());
+    }
+
+    #[test]
+    fn update_missing_note_returns_error() {
+        let store = NotesStore::load(temp_dir("update-missing"));
+        assert!(store.update("does-not-exist".into(), "text".into()).is_err());
+    }
+
+    #[test]
+    fn delete_removes_note_and_its_index_entries() {
+        let store = NotesStore::load(temp_dir("delete"));
+        let note = store
+            .create("to be removed".into(), None, "2024-01-01".into())
+            .unwrap();
+
+        store.delete(note.id.clone()).unwrap();
+
+        let notes = store.list(("2024-01-01".into(), "2024-01-01".into())).unwrap();
+        assert!(notes.is_empty());
+        let hits = store
+            .search("removed".into(), None, "2024-01-01".into(), "2024-01-01".into())
+            .unwrap();
+        assert!(hits.is_empty());
+    }
+
+    #[test]
+    fn delete_missing_note_returns_error() {
+        let store = NotesStore::load(temp_dir("delete-missing"));
+        assert!(store.delete("does-not-exist".into()).is_err());
+    }
+
+    #[test]
+    fn set_storage_dir_merges_existing_notes_at_destination() {
+        let old_dir = temp_dir("old");
+        let store = NotesStore::load(old_dir);
+        let kept = store.create("kept locally".into(), None, "2024-01-01".into()).unwrap();
+
+        let new_dir = temp_dir("new");
+        let mut remote = NotesFile::default();
+        let remote_only = Note {
+            id: "remote-only".into(),
+            content: "synced from another machine".into(),
+            tag: None,
+            date: "2024-01-02".into(),
+            created_at: 1,
+            updated_at: 1,
+        };
+        let mut newer_kept = kept.clone();
+        newer_kept.content = "edited on another machine".into();
+        newer_kept.updated_at = kept.updated_at + 1;
+        remote.notes.insert(remote_only.id.clone(), remote_only);
+        remote.notes.insert(newer_kept.id.clone(), newer_kept.clone());
+        fs::write(
+            new_dir.join(STORE_FILE),
+            serde_json::to_string(&remote).unwrap(),
+        )
+        .unwrap();
+
+        store.set_storage_dir(new_dir.clone()).unwrap();
+
+        let notes = store.list(("2024-01-01".into(), "2024-01-02".into())).unwrap();
+        assert_eq!(notes.len(), 2);
+        let merged_kept = notes.iter().find(|n| n.id == kept.id).unwrap();
+        assert_eq!(merged_kept.content, newer_kept.content);
+        assert!(notes.iter().any(|n| n.id == "remote-only"));
+    }
+
+    #[test]
+    fn set_storage_dir_rolls_back_on_persist_failure() {
+        let old_dir = temp_dir("rollback-old");
+        let store = NotesStore::load(old_dir.clone());
+        store.create("stays put".into(), None, "2024-01-01".into()).unwrap();
+
+        let broken_dir = temp_dir("rollback-new");
+        fs::create_dir_all(broken_dir.join(STORE_FILE)).unwrap();
+
+        let err = store.set_storage_dir(broken_dir).err();
+        assert!(err.is_some());
+        assert_eq!(store.storage_dir().unwrap(), old_dir);
+
+        store.create("still writable".into(), None, "2024-01-02".into()).unwrap();
+        let notes = store.list(("2024-01-01".into(), "2024-01-02".into())).unwrap();
+        assert_eq!(notes.len(), 2);
+    }
+
+    #[test]
+    fn set_storage_dir_does_not_merge_in_memory_when_destination_write_fails() {
+        let old_dir = temp_dir("write-fail-old");
+        let store = NotesStore::load(old_dir.clone());
+        store.create("stays local".into(), None, "2024-01-01".into()).unwrap();
+
+        let new_dir = temp_dir("write-fail-new");
+        let mut remote = NotesFile::default();
+        remote.notes.insert(
+            "remote-only".into(),
+            Note {
+                id: "remote-only".into(),
+                content: "must not leak into the old directory".into(),
+                tag: None,
+                date: "2024-01-02".into(),
+                created_at: 1,
+                updated_at: 1,
+            },
+        );
+        fs::write(
+            new_dir.join(STORE_FILE),
+            serde_json::to_string(&remote).unwrap(),
+        )
+        .unwrap();
+
+        // notes.json is now readable (the merge will find it), but the
+        // directory is then remounted read-only so the write that follows
+        // fails. A plain chmod isn't enough: tests run as root, which
+        // bypasses permission bits on a regular chmod.
+        let new_dir_str = new_dir.to_str().unwrap();
+        let bind_ok = std::process::Command::new("mount")
+            .args(["--bind", new_dir_str, new_dir_str])
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false);
+        let ro_ok = bind_ok
+            && std::process::Command::new("mount")
+                .args(["-o", "remount,ro,bind", new_dir_str])
+                .status()
+                .map(|s| s.success())
+                .unwrap_or(false);
+        if !ro_ok {
+            if bind_ok {
+                let _ = std::process::Command::new("umount").arg(new_dir_str).status();
+            }
+            eprintln!("skipping: read-only bind mount unavailable in this environment");
+            return;
+        }
+
+        let result = store.set_storage_dir(new_dir.clone());
+        let _ = std::process::Command::new("umount").arg(new_dir_str).status();
+
+        assert!(result.is_err());
+        assert_eq!(store.storage_dir().unwrap(), old_dir);
+
+        // The failed merge must not have been swapped into `data.file`: the
+        // next successful persist should only contain locally-created notes.
+        store.create("still only local".into(), None, "2024-01-03".into()).unwrap();
+        let notes = store.list(("2024-01-01".into(), "2024-01-03".into())).unwrap();
+        assert_eq!(notes.len(), 2);
+        assert!(notes.iter().all(|n| n.id != "remote-only"));
+    }
+
+    #[test]
+    fn search_intersects_terms_and_ranks_by_frequency() {
+        let store = NotesStore::load(temp_dir("search"));
+        store
+            .create("cats and dogs, dogs everywhere".into(), None, "2024-01-01".into())
+            .unwrap();
+        store
+            .create("just a dog".into(), None, "2024-01-02".into())
+            .unwrap();
+
+        let hits = store
+            .search("dogs".into(), None, "2024-01-01".into(), "2024-01-02".into())
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].score, 2);
+
+        let none = store
+            .search("cats dog".into(), None, "2024-01-01".into(), "2024-01-02".into())
+            .unwrap();
+        assert!(none.is_empty());
+    }
+}
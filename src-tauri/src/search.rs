@@ -0,0 +1,123 @@
+// 全文检索相关的纯函数：分词、索引条目、摘要高亮
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub note_id: String,
+    pub score: u32,
+    pub snippet: String,
+    pub match_start: usize,
+    pub match_end: usize,
+    pub created_at: i64,
+}
+
+const SNIPPET_RADIUS: usize = 40;
+
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_lowercase())
+        .collect()
+}
+
+/// Term frequencies for one note's content, used to populate the inverted index.
+pub fn index_terms(content: &str) -> HashMap<String, u32> {
+    let mut terms = HashMap::new();
+    for term in tokenize(content) {
+        *terms.entry(term).or_insert(0) += 1;
+    }
+    terms
+}
+
+/// Returns each token's lowercased text and byte span, split on the same
+/// non-alphanumeric boundaries as [`tokenize`], so callers can match whole
+/// words rather than arbitrary substrings.
+fn token_spans(content: &str) -> Vec<(usize, usize, String)> {
+    let mut spans = Vec::new();
+    let mut start: Option<usize> = None;
+    for (i, c) in content.char_indices() {
+        if c.is_alphanumeric() {
+            start.get_or_insert(i);
+        } else if let Some(s) = start.take() {
+            spans.push((s, i, content[s..i].to_lowercase()));
+        }
+    }
+    if let Some(s) = start {
+        spans.push((s, content.len(), content[s..].to_lowercase()));
+    }
+    spans
+}
+
+/// Finds the first whole-word match of any query term in `content` and
+/// returns a surrounding snippet plus the match's byte offsets within it.
+pub fn make_snippet(content: &str, terms: &[String]) -> (String, usize, usize) {
+    let best = token_spans(content)
+        .into_iter()
+        .find(|(_, _, word)| terms.iter().any(|term| term == word))
+        .map(|(start, end, _)| (start, end));
+
+    let Some((start, end)) = best else {
+        let to = ceil_char_boundary(content, SNIPPET_RADIUS.min(content.len()));
+        return (content[..to].to_string(), 0, 0);
+    };
+
+    let from = floor_char_boundary(content, start.saturating_sub(SNIPPET_RADIUS));
+    let to = ceil_char_boundary(content, (end + SNIPPET_RADIUS).min(content.len()));
+    (content[from..to].to_string(), start - from, end - from)
+}
+
+fn floor_char_boundary(s: &str, mut idx: usize) -> usize {
+    while idx > 0 && !s.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+fn ceil_char_boundary(s: &str, mut idx: usize) -> usize {
+    while idx < s.len() && !s.is_char_boundary(idx) {
+        idx += 1;
+    }
+    idx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_splits_on_non_alphanumeric_and_lowercases() {
+        assert_eq!(
+            tokenize("Cats, dogs & CAT-fish!"),
+            vec!["cats", "dogs", "cat", "fish"]
+        );
+    }
+
+    #[test]
+    fn index_terms_counts_repeated_tokens() {
+        let terms = index_terms("dog dog cat");
+        assert_eq!(terms.get("dog"), Some(&2));
+        assert_eq!(terms.get("cat"), Some(&1));
+    }
+
+    #[test]
+    fn make_snippet_matches_whole_word_not_a_containing_substring() {
+        let terms = vec!["cat".to_string()];
+        let (snippet, start, end) = make_snippet("this is a category, not a cat", &terms);
+        assert_eq!(&snippet[start..end], "cat");
+        // The match must be the standalone "cat" near the end, not the
+        // "cat" inside "category" earlier in the string.
+        assert!(snippet.ends_with("cat"));
+    }
+
+    #[test]
+    fn make_snippet_falls_back_to_note_start_when_no_term_matches() {
+        let terms = vec!["missing".to_string()];
+        let (snippet, start, end) = make_snippet("no relevant terms here", &terms);
+        assert_eq!(start, 0);
+        assert_eq!(end, 0);
+        assert!(snippet.starts_with("no relevant"));
+    }
+}
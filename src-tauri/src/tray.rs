@@ -0,0 +1,85 @@
+// 应用菜单与系统托盘
+
+use tauri::menu::{Menu, MenuEvent, MenuItem, Submenu};
+use tauri::tray::{TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+const MENU_NEW_NOTE: &str = "menu-new-note";
+const MENU_OPEN_TODAY: &str = "menu-open-today";
+const MENU_EXPORT: &str = "menu-export";
+const TRAY_SHOW: &str = "tray-show";
+const TRAY_NEW_NOTE: &str = "tray-new-note";
+const TRAY_QUIT: &str = "tray-quit";
+
+pub fn build_menu(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let new_note = MenuItem::with_id(app, MENU_NEW_NOTE, "New Note", true, None::<&str>)?;
+    let open_today = MenuItem::with_id(app, MENU_OPEN_TODAY, "Open Today", true, None::<&str>)?;
+    let export = MenuItem::with_id(app, MENU_EXPORT, "Export", true, None::<&str>)?;
+    let file_menu = Submenu::with_items(app, "File", true, &[&new_note, &open_today, &export])?;
+
+    let edit_menu = Submenu::with_items(
+        app,
+        "Edit",
+        true,
+        &[] as &[&dyn tauri::menu::IsMenuItem<Wry>],
+    )?;
+    let help_menu = Submenu::with_items(
+        app,
+        "Help",
+        true,
+        &[] as &[&dyn tauri::menu::IsMenuItem<Wry>],
+    )?;
+
+    Menu::with_items(app, &[&file_menu, &edit_menu, &help_menu])
+}
+
+pub fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let topic = match event.id().as_ref() {
+        MENU_NEW_NOTE => "menu://new-note",
+        MENU_OPEN_TODAY => "menu://open-today",
+        MENU_EXPORT => "menu://export",
+        _ => return,
+    };
+    let _ = app.emit(topic, ());
+}
+
+pub fn build_tray(app: &AppHandle) -> tauri::Result<()> {
+    let show = MenuItem::with_id(app, TRAY_SHOW, "Show", true, None::<&str>)?;
+    let new_note = MenuItem::with_id(app, TRAY_NEW_NOTE, "New quick note", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, TRAY_QUIT, "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&show, &new_note, &quit])?;
+
+    TrayIconBuilder::new()
+        .icon(
+            app.default_window_icon()
+                .cloned()
+                .ok_or(tauri::Error::InvalidIcon(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "missing default window icon",
+                )))?,
+        )
+        .menu(&menu)
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            TRAY_SHOW => show_main_window(app),
+            TRAY_NEW_NOTE => {
+                let _ = app.emit("menu://new-note", ());
+            }
+            TRAY_QUIT => app.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click { .. } = event {
+                show_main_window(tray.app_handle());
+            }
+        })
+        .build(app)?;
+
+    Ok(())
+}
+
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}